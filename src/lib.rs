@@ -1,5 +1,117 @@
+use std::mem::MaybeUninit;
+
+#[cfg(feature = "mmap")]
+mod mirrored;
+
+/// the backing allocation for a [`SubjectBuffer`]. `Heap` is the portable
+/// default: discarding bytes from the front is a `copy_within` shift of the
+/// live window down to offset 0. `Mirrored` maps the same physical pages
+/// twice back-to-back, so discarding is just advancing `head` and the live
+/// window never needs to move.
+///
+/// `Heap`'s spare capacity is left uninitialized - only the bytes that have
+/// actually been read (or the zeroed lookbehind padding) are ever exposed.
+/// `Mirrored` doesn't need this: the kernel already hands back zeroed pages
+/// on demand, so there's no whole-capacity zeroing cost to avoid there.
+enum Storage {
+    Heap(Box<[MaybeUninit<u8>]>),
+    #[cfg(feature = "mmap")]
+    Mirrored(mirrored::MirroredBuffer),
+}
+
+impl Storage {
+    /// `capacity` bytes of storage with the first `zeroed_prefix` bytes
+    /// (the synthetic pre-start lookbehind padding) genuinely zeroed; the
+    /// rest is left uninitialized on the `Heap` backend
+    fn with_capacity(capacity: usize, zeroed_prefix: usize) -> Self {
+        #[cfg(feature = "mmap")]
+        {
+            if let Ok(m) = mirrored::MirroredBuffer::with_capacity(capacity) {
+                return Storage::Mirrored(m);
+            }
+            // mapping the same pages twice isn't available on every platform
+            // or may fail (e.g. address space exhaustion) - fall back
+        }
+        let mut buf = Box::new_uninit_slice(capacity);
+        for b in &mut buf[..zeroed_prefix] {
+            b.write(0);
+        }
+        Storage::Heap(buf)
+    }
+
+    /// the `len` live bytes starting at `head`, as a single contiguous slice
+    fn window(&self, head: usize, len: usize) -> &[u8] {
+        match self {
+            // SAFETY: every byte `read` has ever exposed through `head..head+len`
+            // was either copied from a previously initialized byte or came from
+            // the zeroed lookbehind prefix set up in `with_capacity`
+            Storage::Heap(buf) => unsafe { assume_init(&buf[head..head + len]) },
+            #[cfg(feature = "mmap")]
+            Storage::Mirrored(m) => m.window(head, len),
+        }
+    }
+
+    /// the writable space immediately following the live window, up to
+    /// `capacity` (the logical capacity, not necessarily the backend's
+    /// physical capacity)
+    fn fill_region(&mut self, head: usize, len: usize, capacity: usize) -> &mut [u8] {
+        match self {
+            // the destination is only ever passed to `Read::read`, which is
+            // trusted by contract to write into it before reading from it
+            Storage::Heap(buf) => assume_init_mut(&mut buf[head + len..capacity]),
+            #[cfg(feature = "mmap")]
+            Storage::Mirrored(m) => &mut m.fill_region(head, len)[..capacity - len],
+        }
+    }
+
+    /// grows to `new_capacity`, re-seating the `len` live bytes starting at
+    /// `head` to the front (offset 0) of the new storage
+    fn grow(&mut self, head: usize, len: usize, new_capacity: usize) -> Result<(), Box<dyn std::error::Error>> {
+        match self {
+            Storage::Heap(buf) => {
+                let mut new_buf = Box::new_uninit_slice(new_capacity);
+                // SAFETY: `buf[head..head+len]` is the live, initialized window;
+                // copying it into the front of the (otherwise uninitialized)
+                // new allocation is sound and leaves the new tail untouched
+                unsafe {
+                    let src = buf[head..head + len].as_ptr() as *const u8;
+                    let dst = new_buf[..len].as_mut_ptr() as *mut u8;
+                    std::ptr::copy_nonoverlapping(src, dst, len);
+                }
+                *buf = new_buf;
+                Ok(())
+            }
+            #[cfg(feature = "mmap")]
+            Storage::Mirrored(m) => Ok(m.grow(head, len, new_capacity)?),
+        }
+    }
+}
+
+/// SAFETY: caller must ensure every byte in `slice` has been initialized
+unsafe fn assume_init(slice: &[MaybeUninit<u8>]) -> &[u8] {
+    std::slice::from_raw_parts(slice.as_ptr() as *const u8, slice.len())
+}
+
+/// `u8` has no validity invariant, so this is treated as sound regardless of
+/// whether `slice` is initialized and is exposed as a safe function; callers
+/// still end up handing out a `&mut [u8]` over memory that may not be
+/// initialized
+fn assume_init_mut(slice: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+    unsafe { std::slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut u8, slice.len()) }
+}
+
 pub struct SubjectBuffer {
-    buffer: Box<[u8]>,
+    storage: Storage,
+
+    /// offset of the live window's first byte within `storage`. always 0 for
+    /// the `Heap` backend, which shifts bytes down instead of moving the head
+    head: usize,
+
+    /// the logical capacity of `storage`. may be smaller than the backend's
+    /// actual physical capacity (e.g. an mmap backend rounded up to a page
+    /// boundary), and is what `min_capacity`/`max_capacity` growth decisions
+    /// are made against
+    capacity: usize,
 
     /// buffer capacity will be smaller than min_capacity before first read,
     /// but will be greater or equal after first read
@@ -14,9 +126,17 @@ pub struct SubjectBuffer {
     /// the number of bytes in the buffer
     len: usize,
 
-    /// indicates the position of the buffer's beginning inside of the source.  
+    /// indicates the position of the buffer's beginning inside of the source.
     /// it may start as a negative value, as the start is padded with zeroed lookbehind bytes
     source_offset: i128,
+
+    /// the lowest absolute source offset at which lookbehind can be trusted.
+    /// starts at 0 (matching the synthetic zero padding at the very start of
+    /// the source); [`SubjectBuffer::skip`] raises it whenever it jumps over
+    /// a gap, and [`SubjectBuffer::seek_to`] resets it to wherever the new
+    /// window's real (non-padded) lookbehind begins, since in both cases the
+    /// bytes just before that point are no longer genuine immediate context
+    valid_lookbehind_from: i128,
 }
 
 impl SubjectBuffer {
@@ -31,17 +151,20 @@ impl SubjectBuffer {
         // no special handling or assertions is required for max_capacity
 
         Ok(Self {
-            buffer: vec![0; max_lookbehind].into_boxed_slice(),
+            storage: Storage::with_capacity(max_lookbehind, max_lookbehind),
+            head: 0,
+            capacity: max_lookbehind,
             min_capacity,
             max_capacity,
             max_lookbehind,
             len: max_lookbehind,
             source_offset: -(max_lookbehind as i128),
+            valid_lookbehind_from: 0,
         })
     }
 
     pub fn buffer<'a>(&'a self) -> &'a [u8] {
-        &self.buffer[..self.len]
+        self.storage.window(self.head, self.len)
     }
 
     pub fn len(&self) -> usize {
@@ -68,11 +191,16 @@ impl SubjectBuffer {
     ///  - on first read, this must be equal to the max lookbehind (zero for no lookbehind)
     ///  - otherwise, point to beginning of an incomplete match (not including lookbehind)
     ///  - otherwise, on no matches remaining, point to the end of the buffer (get_size())
-    /// 
+    ///
     /// match offset will be modified as the buffer is shifted, to keep it in sync.
     ///
+    /// keeps reading until the available space is full, input_source hits
+    /// EOF, or input_source would block - so a source that only ever returns
+    /// short reads (a socket, a pipe) still leaves the buffer maximally
+    /// filled before matching inspects it
+    ///
     /// returns true iff the input is complete (and 0 bytes were added to the buffer)
-    /// 
+    ///
     /// 1. read
     /// 2. <do pattern matching>
     /// 3. verify_match
@@ -87,7 +215,7 @@ impl SubjectBuffer {
         if *match_offset <= self.max_lookbehind {
             // atypical case. no bytes can safely be discarded from the buffer. this
             // is handled by expanding the size of the buffer
-            let next_cap = if self.buffer.len() < self.min_capacity {
+            let next_cap = if self.capacity < self.min_capacity {
                 // this always occurs on first read.
 
                 // buffer len was originally set to max_lookbehind.
@@ -95,7 +223,7 @@ impl SubjectBuffer {
                 // this is checked in the ctor
                 self.min_capacity
             } else {
-                let next_cap = self.buffer.len() * 2;
+                let next_cap = self.capacity * 2;
                 if next_cap > self.max_capacity {
                     return Err(Box::new(std::io::Error::new(
                         std::io::ErrorKind::Other,
@@ -105,39 +233,207 @@ impl SubjectBuffer {
                 next_cap
             };
 
-            let mut new_buffer = vec![0; next_cap].into_boxed_slice();
-            (&mut new_buffer[0..self.len]).copy_from_slice(&self.buffer[0..self.len]);
-            self.buffer = new_buffer
+            self.storage.grow(self.head, self.len, next_cap)?;
+            self.head = 0;
+            self.capacity = next_cap;
         } else {
             // typical case. see readme docstring for details
             let num_bytes_discarded = *match_offset - self.max_lookbehind;
             debug_assert!(num_bytes_discarded > 0); // guarded against, above
-            self.buffer.copy_within(num_bytes_discarded..self.len, 0);
-            self.len -= num_bytes_discarded;
+            self.discard(num_bytes_discarded);
             *match_offset -= num_bytes_discarded;
-            self.source_offset += num_bytes_discarded as i128;
         }
 
-        // more space was made above. fill it
-        let len = self.buffer.len();
-        let mut read_dst = &mut self.buffer[self.len..len];
-        match input_source.read(&mut read_dst) {
-            Ok(read_ret) => {
-                self.len += read_ret;
-                return Ok(read_ret==0);
-            },
-            Err(e) => return Err(Box::new(e)),
+        // more space was made above. fill it, looping until the destination
+        // is full, the source hits EOF, or it would block
+        let len_before_fill = self.len;
+        let eof = loop {
+            let read_dst = self.storage.fill_region(self.head, self.len, self.capacity);
+            if read_dst.is_empty() {
+                break false;
+            }
+            match input_source.read(read_dst) {
+                Ok(0) => break true,
+                Ok(read_ret) => self.len += read_ret,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break false,
+                Err(e) => return Err(Box::new(e)),
+            }
+        };
+        Ok(eof && self.len == len_before_fill)
+    }
+
+    /// discards `num_bytes_discarded` live bytes from the front of the
+    /// buffer (the bytes after them, including the retained lookbehind, are
+    /// kept), advancing `source_offset` to match
+    fn discard(&mut self, num_bytes_discarded: usize) {
+        match &mut self.storage {
+            Storage::Heap(buf) => buf.copy_within(num_bytes_discarded..self.len, 0),
+            #[cfg(feature = "mmap")]
+            Storage::Mirrored(m) => self.head = (self.head + num_bytes_discarded) % m.capacity(),
+        }
+        self.len -= num_bytes_discarded;
+        self.source_offset += num_bytes_discarded as i128;
+    }
+
+    /// drains bytes already buffered beyond the retained lookbehind towards
+    /// a skip of `n` source bytes, without touching `input_source`. returns
+    /// however much of `n` is left to be skipped directly from the source.
+    fn skip_buffered(&mut self, n: u64) -> u64 {
+        let already_buffered = (self.len - self.max_lookbehind) as u64;
+        let from_buffer = already_buffered.min(n);
+        if from_buffer > 0 {
+            self.discard(from_buffer as usize);
+        }
+        n - from_buffer
+    }
+
+    /// discards `n` source bytes without ever reading them into the buffer,
+    /// advancing `source_offset` accordingly. bytes already buffered beyond
+    /// the retained lookbehind are dropped first; anything left over is
+    /// consumed straight from `input_source` in fixed-size chunks, without
+    /// growing or otherwise touching the buffer.
+    ///
+    /// once any part of `n` is skipped directly from `input_source` (i.e.
+    /// buffered data alone wasn't enough to cover it), the bytes currently
+    /// retained as lookbehind sat just before that gap, so they no longer
+    /// represent genuine immediate context: `verify_match` rejects them the
+    /// same way it rejects the synthetic start padding, even if this call
+    /// only manages to skip part of the gap before returning early or
+    /// erroring out.
+    ///
+    /// if `input_source` also implements [`std::io::Seek`], prefer
+    /// [`SubjectBuffer::skip_seek`], which skips the unbuffered remainder
+    /// with a single seek instead of streaming it through a scratch buffer.
+    pub fn skip<R: std::io::Read>(&mut self, n: u64, input_source: &mut R) -> Result<(), Box<dyn std::error::Error>> {
+        let mut n = self.skip_buffered(n);
+        if n > 0 {
+            let mut scratch = [0u8; 4096];
+            let mut pending_err = None;
+            while n > 0 {
+                let chunk = (scratch.len() as u64).min(n) as usize;
+                match input_source.read(&mut scratch[..chunk]) {
+                    Ok(0) => break, // input_source is exhausted; nothing left to skip
+                    Ok(read_ret) => {
+                        self.source_offset += read_ret as i128;
+                        n -= read_ret as u64;
+                    }
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(e) => {
+                        pending_err = Some(e);
+                        break;
+                    }
+                }
+            }
+            // even a partial, interrupted, or erroring skip already moved
+            // source_offset past data the buffer never saw, so whatever
+            // progress was made must invalidate the current lookbehind
+            // before this call returns, error or not
+            self.valid_lookbehind_from = self.get_absolute_offset(self.max_lookbehind);
+            if let Some(e) = pending_err {
+                return Err(Box::new(e));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// like [`SubjectBuffer::skip`], but for sources that also implement
+    /// [`std::io::Seek`]: the unbuffered remainder of `n` is skipped with a
+    /// single `seek(SeekFrom::Current(..))` instead of being streamed
+    /// through a scratch buffer a chunk at a time.
+    pub fn skip_seek<R: std::io::Read + std::io::Seek>(&mut self, n: u64, input_source: &mut R) -> Result<(), Box<dyn std::error::Error>> {
+        let n = self.skip_buffered(n);
+        if n > 0 {
+            let delta: i64 = n
+                .try_into()
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "skip amount too large to seek in one call"))?;
+            input_source.seek(std::io::SeekFrom::Current(delta))?;
+            self.source_offset += n as i128;
+            self.valid_lookbehind_from = self.get_absolute_offset(self.max_lookbehind);
+        }
+
+        Ok(())
+    }
+
+    /// repositions the window to start matching at `absolute_offset` in
+    /// `input_source`, discarding whatever is currently buffered. this
+    /// follows the buffer-invalidation-on-seek pattern of
+    /// `std::io::BufReader::seek`/`discard_buffer`: the old contents are
+    /// simply dropped rather than reused, since after an arbitrary jump
+    /// they have nothing to do with the new position.
+    ///
+    /// seeks `input_source` to `absolute_offset - max_lookbehind` (clamped
+    /// at 0) and re-reads up to `max_lookbehind` bytes from there to prime
+    /// a genuine lookbehind window; any shortfall introduced by the clamp
+    /// (seeking near the start of the source) is filled with the same
+    /// synthetic zero padding used by [`SubjectBuffer::new`], so a seek
+    /// near the start of the stream still yields a valid lookbehind region.
+    pub fn seek_to<R: std::io::Read + std::io::Seek>(
+        &mut self,
+        absolute_offset: i128,
+        input_source: &mut R,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let lookbehind_start = absolute_offset - self.max_lookbehind as i128;
+        let seek_target = lookbehind_start.max(0);
+        // clamped to max_lookbehind: a sufficiently negative absolute_offset
+        // (e.g. one read back from get_absolute_offset/verify_match for a
+        // match inside the synthetic lookbehind padding) would otherwise
+        // make `deficit` exceed max_lookbehind and underflow `to_read` below
+        let deficit = (seek_target - lookbehind_start).min(self.max_lookbehind as i128) as usize;
+
+        input_source.seek(std::io::SeekFrom::Start(seek_target as u64))?;
+
+        self.storage = Storage::with_capacity(self.capacity, deficit);
+        self.head = 0;
+
+        let mut primed_lookbehind = deficit;
+        let to_read = self.max_lookbehind - deficit;
+        let mut filled = 0;
+        if to_read > 0 {
+            let read_dst = &mut self.storage.fill_region(0, primed_lookbehind, self.capacity)[..to_read];
+            while filled < to_read {
+                match input_source.read(&mut read_dst[filled..]) {
+                    Ok(0) => break, // input_source is shorter than absolute_offset
+                    Ok(n) => filled += n,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(e) => return Err(Box::new(e)),
+                }
+            }
+            primed_lookbehind += filled;
+        }
+
+        // the source may be shorter than absolute_offset (e.g. resuming a
+        // scan near the tail of a still-growing file) - zero-pad the
+        // shortfall the same way the left-side clamp is padded, so `len`
+        // always equals `max_lookbehind` just like after `new`, keeping the
+        // `len >= max_lookbehind` invariant that `read`/`skip` rely on
+        if primed_lookbehind < self.max_lookbehind {
+            let shortfall = self.max_lookbehind - primed_lookbehind;
+            self.storage.fill_region(0, primed_lookbehind, self.capacity)[..shortfall].fill(0);
+            primed_lookbehind = self.max_lookbehind;
         }
+
+        self.len = primed_lookbehind;
+        self.source_offset = absolute_offset - self.max_lookbehind as i128;
+        // a short read means part of the lookbehind is now synthetic padding
+        // sitting right up against absolute_offset rather than at the far
+        // (left) end; since valid_lookbehind_from can only express a single
+        // floor, not a floor-and-ceiling, the whole lookbehind is rejected
+        // in that case rather than just the left clamp
+        self.valid_lookbehind_from = if filled == to_read { seek_target } else { absolute_offset };
+
+        Ok(())
     }
 
     /// the beginning of the source is padded with null bytes to always have a
-    /// sufficient lookbehind length. this function checks that a match's
-    /// lookbehind does not include this fake padding
+    /// sufficient lookbehind length, and a `skip` or `seek_to` may leave
+    /// stale bytes behind as well. this function checks that a match's
+    /// lookbehind does not reach into any kind of untrustworthy padding
     pub fn verify_match(&self, match_begin_with_lookbehind: usize) -> bool {
-        if self.source_offset >= 0 {
-            return true
-        }
-        return (match_begin_with_lookbehind as i128) >= -self.source_offset
+        self.get_absolute_offset(match_begin_with_lookbehind) >= self.valid_lookbehind_from
     }
 
     /// a match offset is relative to the beginning of the matching buffer.
@@ -292,4 +588,191 @@ mod tests {
             Err(_) => assert!(true),
         }
     }
+
+    /// a reader that only ever hands back one byte per call, regardless of
+    /// how much space it's given
+    struct OneByteAtATime<'a>(&'a [u8]);
+
+    impl<'a> std::io::Read for OneByteAtATime<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.0.is_empty() || buf.is_empty() {
+                return Ok(0);
+            }
+            buf[0] = self.0[0];
+            self.0 = &self.0[1..];
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn read_fills_available_space_despite_short_reads() {
+        let mut buffer = SubjectBuffer::new(20, 0, 0).unwrap();
+        let data: &[u8] = b"Hello, world!";
+        let mut reader = OneByteAtATime(data);
+
+        let mut match_offset = buffer.max_lookbehind();
+        let ret = buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert!(!ret);
+        // despite the reader only ever returning one byte per call, all of
+        // the source's bytes were buffered in a single `read`
+        assert_eq!(buffer.buffer(), data);
+
+        let mut match_offset = buffer.len();
+        let ret = buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert!(ret); // input complete, zero new bytes
+    }
+
+    #[test]
+    fn skip_within_buffered_data_keeps_lookbehind_valid() {
+        let mut buffer = SubjectBuffer::new(4, 64, 2).unwrap();
+        let data: &[u8] = b"0123456789";
+        let mut reader = Cursor::new(data);
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[0, 0, b'0', b'1']);
+
+        // both bytes past the lookbehind are already buffered - no gap in
+        // input_source is introduced, so the lookbehind stays trustworthy
+        buffer.skip(2, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[b'0', b'1']);
+        assert_eq!(buffer.get_absolute_offset(buffer.max_lookbehind()), 2);
+        assert!(buffer.verify_match(0));
+        assert!(buffer.verify_match(1));
+    }
+
+    #[test]
+    fn skip_past_buffered_data_invalidates_lookbehind() {
+        let mut buffer = SubjectBuffer::new(4, 64, 2).unwrap();
+        let data: &[u8] = b"0123456789";
+        let mut reader = Cursor::new(data);
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[0, 0, b'0', b'1']);
+
+        // only 2 bytes beyond the lookbehind are buffered ('0', '1');
+        // skipping 5 drains those, then skips 3 more straight from
+        // input_source, over a gap ('2', '3', '4')
+        buffer.skip(5, &mut reader).unwrap();
+        assert_eq!(buffer.get_absolute_offset(buffer.max_lookbehind()), 5);
+
+        assert!(!buffer.verify_match(0)); // sits before the skipped gap
+        assert!(!buffer.verify_match(1));
+        assert!(buffer.verify_match(2)); // the start of the new lookbehind
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer()[2], b'5');
+    }
+
+    #[test]
+    fn skip_seek_past_buffered_data_invalidates_lookbehind() {
+        let mut buffer = SubjectBuffer::new(4, 64, 2).unwrap();
+        let data: &[u8] = b"0123456789";
+        let mut reader = Cursor::new(data);
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[0, 0, b'0', b'1']);
+
+        // same split as skip_past_buffered_data_invalidates_lookbehind, but
+        // the unbuffered remainder is skipped with a seek instead of reads
+        buffer.skip_seek(5, &mut reader).unwrap();
+        assert_eq!(buffer.get_absolute_offset(buffer.max_lookbehind()), 5);
+
+        assert!(!buffer.verify_match(0));
+        assert!(!buffer.verify_match(1));
+        assert!(buffer.verify_match(2));
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer()[2], b'5');
+    }
+
+    #[test]
+    fn seek_to_middle_of_stream_primes_real_lookbehind() {
+        let mut buffer = SubjectBuffer::new(4, 64, 2).unwrap();
+        let data: &[u8] = b"0123456789";
+        let mut reader = Cursor::new(data);
+
+        // jump to offset 5 - the 2 bytes of real lookbehind ('3', '4') are
+        // read back in, not synthesized
+        buffer.seek_to(5, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[b'3', b'4']);
+        assert_eq!(buffer.get_absolute_offset(buffer.max_lookbehind()), 5);
+        assert!(buffer.verify_match(0));
+        assert!(buffer.verify_match(1));
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[b'3', b'4', b'5', b'6']);
+    }
+
+    #[test]
+    fn seek_to_near_start_pads_deficit_with_zeros() {
+        let mut buffer = SubjectBuffer::new(4, 64, 3).unwrap();
+        let data: &[u8] = b"0123456789";
+        let mut reader = Cursor::new(data);
+
+        // offset 1 - max_lookbehind (3) is -2, clamped to 0: 2 bytes of
+        // synthetic zero padding, then 1 real byte ('0') read back in
+        buffer.seek_to(1, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[0, 0, b'0']);
+        assert_eq!(buffer.get_absolute_offset(buffer.max_lookbehind()), 1);
+
+        assert!(!buffer.verify_match(0)); // sits in the synthetic padding
+        assert!(!buffer.verify_match(1));
+        assert!(buffer.verify_match(2)); // the one genuine lookbehind byte
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[0, 0, b'0', b'1']);
+    }
+
+    #[test]
+    fn seek_to_negative_absolute_offset_is_all_padding() {
+        let mut buffer = SubjectBuffer::new(4, 64, 3).unwrap();
+        let data: &[u8] = b"0123456789";
+        let mut reader = Cursor::new(data);
+
+        // -5 - max_lookbehind (3) is -8, clamped to 0: the full lookbehind
+        // is synthetic padding, with nothing genuine left to read back in
+        buffer.seek_to(-5, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[0, 0, 0]);
+        assert_eq!(buffer.get_absolute_offset(buffer.max_lookbehind()), -5);
+
+        assert!(!buffer.verify_match(0));
+        assert!(!buffer.verify_match(1));
+        assert!(!buffer.verify_match(2));
+
+        let mut match_offset = buffer.max_lookbehind();
+        buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert_eq!(buffer.buffer(), &[0, 0, 0, b'0']);
+    }
+
+    #[test]
+    fn seek_to_past_end_of_stream_pads_the_short_read_and_keeps_len_invariant() {
+        let mut buffer = SubjectBuffer::new(4, 64, 3).unwrap();
+        let data: &[u8] = b"0123456789"; // only 10 bytes long
+        let mut reader = Cursor::new(data);
+
+        // the source has nothing at or after offset 97, so all 3 lookbehind
+        // bytes come up short and must be zero-padded on the right
+        buffer.seek_to(100, &mut reader).unwrap();
+        assert_eq!(buffer.len(), buffer.max_lookbehind()); // len invariant held
+        assert_eq!(buffer.buffer(), &[0, 0, 0]);
+        assert_eq!(buffer.get_absolute_offset(buffer.max_lookbehind()), 100);
+
+        // the whole lookbehind is now untrustworthy, not just the short tail
+        assert!(!buffer.verify_match(0));
+        assert!(!buffer.verify_match(1));
+        assert!(!buffer.verify_match(2));
+
+        // a subsequent read() must not panic on the len/max_lookbehind
+        // invariant, and correctly reports the source as exhausted
+        let mut match_offset = buffer.max_lookbehind();
+        let ret = buffer.read(&mut match_offset, &mut reader).unwrap();
+        assert!(ret);
+    }
 }
@@ -0,0 +1,377 @@
+//! A mirrored (double-mapped) ring buffer: `capacity` real bytes, with the
+//! same physical pages mapped a second time immediately afterwards. This
+//! makes any window `[head..head+len]` with `head < capacity` and
+//! `len <= capacity` addressable as a single contiguous slice even when it
+//! logically wraps past the end of the real allocation, so discarding bytes
+//! from the front is a pointer bump (`head = (head + n) % capacity`) instead
+//! of a `copy_within` shift of the live bytes.
+
+use std::io;
+
+// `memfd_create` is only exposed by `libc` for Linux-like targets and
+// FreeBSD - not for macOS, the BSDs besides FreeBSD, or Solaris, all of
+// which also match `cfg(unix)`. Gate this implementation narrowly so those
+// other Unix targets fall through to the `imp` stub below (and, in turn,
+// `Storage::with_capacity`'s `Heap` fallback) instead of failing to build.
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+mod imp {
+    use super::*;
+    use std::ffi::CString;
+    use std::ptr;
+
+    pub struct Mapping {
+        ptr: *mut u8,
+        capacity: usize,
+    }
+
+    // the mapping owns its pages outright and does not alias any other
+    // Rust-visible allocation
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+
+    impl Mapping {
+        pub fn new(capacity: usize) -> io::Result<Self> {
+            let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+            let capacity = round_up(capacity.max(1), page_size);
+
+            unsafe {
+                let fd = anon_fd(capacity)?;
+
+                // reserve 2 * capacity of contiguous address space up front, so the
+                // two MAP_FIXED mappings below are guaranteed to land back-to-back
+                let base = libc::mmap(
+                    ptr::null_mut(),
+                    capacity * 2,
+                    libc::PROT_NONE,
+                    libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+                    -1,
+                    0,
+                );
+                if base == libc::MAP_FAILED {
+                    libc::close(fd);
+                    return Err(io::Error::last_os_error());
+                }
+
+                let first = libc::mmap(
+                    base,
+                    capacity,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_FIXED | libc::MAP_SHARED,
+                    fd,
+                    0,
+                );
+                if first == libc::MAP_FAILED {
+                    libc::munmap(base, capacity * 2);
+                    libc::close(fd);
+                    return Err(io::Error::last_os_error());
+                }
+
+                let second = libc::mmap(
+                    base.add(capacity),
+                    capacity,
+                    libc::PROT_READ | libc::PROT_WRITE,
+                    libc::MAP_FIXED | libc::MAP_SHARED,
+                    fd,
+                    0,
+                );
+                if second == libc::MAP_FAILED {
+                    libc::munmap(base, capacity * 2);
+                    libc::close(fd);
+                    return Err(io::Error::last_os_error());
+                }
+
+                // the mappings keep the backing memory alive; the descriptor itself
+                // is no longer needed
+                libc::close(fd);
+
+                Ok(Self { ptr: base as *mut u8, capacity })
+            }
+        }
+
+        pub fn capacity(&self) -> usize {
+            self.capacity
+        }
+
+        pub fn as_ptr(&self) -> *const u8 {
+            self.ptr
+        }
+
+        pub fn as_mut_ptr(&mut self) -> *mut u8 {
+            self.ptr
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            unsafe {
+                libc::munmap(self.ptr as *mut libc::c_void, self.capacity * 2);
+            }
+        }
+    }
+
+    unsafe fn anon_fd(size: usize) -> io::Result<libc::c_int> {
+        // an unlinked, in-memory file is the simplest way to get one physical
+        // region that can legally be mapped twice
+        let name = CString::new("matching-buffer").unwrap();
+        let fd = libc::memfd_create(name.as_ptr(), 0);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        if libc::ftruncate(fd, size as libc::off_t) != 0 {
+            let e = io::Error::last_os_error();
+            libc::close(fd);
+            return Err(e);
+        }
+        Ok(fd)
+    }
+
+    fn round_up(n: usize, align: usize) -> usize {
+        n.div_ceil(align) * align
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use super::*;
+    use std::ptr;
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Memory::{
+        CreateFileMappingW, MapViewOfFileEx, UnmapViewOfFile, VirtualAlloc, VirtualFree,
+        FILE_MAP_ALL_ACCESS, MEM_RELEASE, MEM_RESERVE, PAGE_READWRITE,
+    };
+
+    pub struct Mapping {
+        file_mapping: HANDLE,
+        ptr: *mut u8,
+        capacity: usize,
+    }
+
+    unsafe impl Send for Mapping {}
+    unsafe impl Sync for Mapping {}
+
+    impl Mapping {
+        pub fn new(capacity: usize) -> io::Result<Self> {
+            let page_size = page_size();
+            let capacity = round_up(capacity.max(1), page_size);
+
+            unsafe {
+                let file_mapping = CreateFileMappingW(
+                    INVALID_HANDLE_VALUE,
+                    ptr::null(),
+                    PAGE_READWRITE,
+                    (capacity >> 32) as u32,
+                    capacity as u32,
+                    ptr::null(),
+                );
+                if file_mapping == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+
+                // reserve 2 * capacity of address space, then free it right away so
+                // the two MapViewOfFileEx calls below can claim sub-ranges of it
+                let reservation = VirtualAlloc(ptr::null(), capacity * 2, MEM_RESERVE, PAGE_READWRITE);
+                if reservation.is_null() {
+                    CloseHandle(file_mapping);
+                    return Err(io::Error::last_os_error());
+                }
+                VirtualFree(reservation, 0, MEM_RELEASE);
+
+                let first = MapViewOfFileEx(file_mapping, FILE_MAP_ALL_ACCESS, 0, 0, capacity, reservation);
+                if first.is_null() {
+                    CloseHandle(file_mapping);
+                    return Err(io::Error::last_os_error());
+                }
+
+                let second_addr = (reservation as usize + capacity) as *const core::ffi::c_void;
+                let second = MapViewOfFileEx(file_mapping, FILE_MAP_ALL_ACCESS, 0, 0, capacity, second_addr);
+                if second.is_null() {
+                    UnmapViewOfFile(first);
+                    CloseHandle(file_mapping);
+                    return Err(io::Error::last_os_error());
+                }
+
+                Ok(Self { file_mapping, ptr: reservation as *mut u8, capacity })
+            }
+        }
+
+        pub fn capacity(&self) -> usize {
+            self.capacity
+        }
+
+        pub fn as_ptr(&self) -> *const u8 {
+            self.ptr
+        }
+
+        pub fn as_mut_ptr(&mut self) -> *mut u8 {
+            self.ptr
+        }
+    }
+
+    impl Drop for Mapping {
+        fn drop(&mut self) {
+            unsafe {
+                UnmapViewOfFile(self.ptr as *const core::ffi::c_void);
+                UnmapViewOfFile(self.ptr.add(self.capacity) as *const core::ffi::c_void);
+                CloseHandle(self.file_mapping);
+            }
+        }
+    }
+
+    fn page_size() -> usize {
+        use windows_sys::Win32::System::SystemInformation::GetSystemInfo;
+        unsafe {
+            let mut info = std::mem::zeroed();
+            GetSystemInfo(&mut info);
+            info.dwAllocationGranularity as usize
+        }
+    }
+
+    fn round_up(n: usize, align: usize) -> usize {
+        n.div_ceil(align) * align
+    }
+}
+
+/// no double-mapping primitive is implemented for this platform yet.
+/// `Mapping::new` always fails here, so [`MirroredBuffer::with_capacity`]
+/// always fails too, and callers (namely `Storage::with_capacity`)
+/// transparently fall back to the `Heap` backend instead of failing to
+/// build or panicking at runtime.
+#[cfg(not(any(target_os = "linux", target_os = "freebsd", windows)))]
+mod imp {
+    use super::*;
+
+    pub struct Mapping;
+
+    impl Mapping {
+        pub fn new(_capacity: usize) -> io::Result<Self> {
+            Err(io::Error::new(io::ErrorKind::Unsupported, "mirrored mmap buffer is not implemented on this platform"))
+        }
+
+        pub fn capacity(&self) -> usize {
+            unreachable!("Mapping::new always fails on this platform")
+        }
+
+        pub fn as_ptr(&self) -> *const u8 {
+            unreachable!("Mapping::new always fails on this platform")
+        }
+
+        pub fn as_mut_ptr(&mut self) -> *mut u8 {
+            unreachable!("Mapping::new always fails on this platform")
+        }
+    }
+}
+
+/// mirrored ring-buffer storage backing [`crate::SubjectBuffer`] when the
+/// `mmap` feature is enabled and the platform mapping succeeds
+pub struct MirroredBuffer {
+    mapping: imp::Mapping,
+}
+
+impl MirroredBuffer {
+    pub fn with_capacity(capacity: usize) -> io::Result<Self> {
+        Ok(Self { mapping: imp::Mapping::new(capacity)? })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.mapping.capacity()
+    }
+
+    /// the `len` live bytes starting at `head`, as a single contiguous slice
+    pub fn window(&self, head: usize, len: usize) -> &[u8] {
+        debug_assert!(len <= self.capacity());
+        debug_assert!(head < self.capacity() || self.capacity() == 0);
+        unsafe { std::slice::from_raw_parts(self.mapping.as_ptr().add(head), len) }
+    }
+
+    /// the writable space immediately following the live window, sized to
+    /// never exceed one full lap of the ring
+    pub fn fill_region(&mut self, head: usize, len: usize) -> &mut [u8] {
+        let free = self.capacity() - len;
+        unsafe { std::slice::from_raw_parts_mut(self.mapping.as_mut_ptr().add(head + len), free) }
+    }
+
+    /// remaps to a larger mirrored region, re-seating the live bytes at
+    /// offset 0 of the new mapping
+    pub fn grow(&mut self, head: usize, len: usize, new_capacity: usize) -> io::Result<()> {
+        let mut grown = Self::with_capacity(new_capacity)?;
+        grown.fill_region(0, 0)[..len].copy_from_slice(self.window(head, len));
+        *self = grown;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_reads_contiguously_across_the_real_physical_boundary() {
+        let mut buf = MirroredBuffer::with_capacity(1).unwrap();
+        let capacity = buf.capacity();
+        assert!(capacity > 0);
+
+        // a distinct pattern at the very end and very start of the real
+        // backing memory, so a window straddling the wrap can be checked
+        // byte-for-byte, not just spot-checked
+        {
+            let region = buf.fill_region(0, 0);
+            region[capacity - 4..capacity].copy_from_slice(&[1, 2, 3, 4]);
+            region[0..4].copy_from_slice(&[5, 6, 7, 8]);
+        }
+
+        // head sits 4 bytes before the end of the real mapping; this window
+        // must read the last 4 "real" bytes followed by the mirrored first 4
+        let window = buf.window(capacity - 4, 8);
+        assert_eq!(window, &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn many_discards_wrap_head_past_the_physical_boundary() {
+        let mut buf = MirroredBuffer::with_capacity(1).unwrap();
+        let capacity = buf.capacity();
+        assert!(capacity >= 4096); // real allocations round up to a full page
+
+        // seed every real byte with its own (truncated) index, so a window
+        // can be checked against head's position after wrapping, without
+        // needing to track a separate absolute source offset
+        {
+            let region = buf.fill_region(0, 0);
+            for (i, b) in region.iter_mut().enumerate() {
+                *b = (i % 256) as u8;
+            }
+        }
+
+        // advance head by a step that doesn't evenly divide the page-rounded
+        // capacity, enough times to sweep past the real/mirrored boundary
+        // several times rather than landing on it just once
+        let len = 64;
+        let step = 777usize;
+        let mut head = 0usize;
+        let mut wrapped = false;
+        for _ in 0..(capacity / step * 2 + 2) {
+            let next_head = (head + step) % capacity;
+            if next_head < head {
+                wrapped = true;
+            }
+            head = next_head;
+
+            let window = buf.window(head, len);
+            for (j, b) in window.iter().enumerate() {
+                assert_eq!(*b, ((head + j) % 256) as u8);
+            }
+        }
+        assert!(wrapped, "test step/capacity didn't actually exercise a wraparound");
+    }
+
+    #[test]
+    fn grow_preserves_the_live_window_and_drops_the_old_mapping() {
+        let mut buf = MirroredBuffer::with_capacity(1).unwrap();
+        let small_capacity = buf.capacity();
+
+        buf.fill_region(0, 0)[..5].copy_from_slice(b"hello");
+        buf.grow(0, 5, small_capacity * 2).unwrap();
+
+        assert!(buf.capacity() >= small_capacity * 2);
+        assert_eq!(buf.window(0, 5), b"hello");
+    }
+}